@@ -0,0 +1,203 @@
+//! Configurable exponential backoff with full jitter, used by the backoff
+//! states (`ImagePullBackoff`, `VolumeMountBackoff`, `Error`) instead of the
+//! unconditional, infinite `delay_for(30s)` they used to run.
+
+use rand::Rng;
+use std::time::Duration;
+
+/// A full-jitter exponential backoff policy: `sleep = rand(0, min(cap, base
+/// * multiplier^attempt))`. See <https://aws.amazon.com/blogs/architecture/exponential-backoff-and-jitter/>.
+#[derive(Clone, Debug)]
+pub struct BackoffConfig {
+    /// Delay before the first retry.
+    pub base: Duration,
+    /// How much the delay grows per attempt.
+    pub multiplier: f64,
+    /// Upper bound on the delay, regardless of attempt count.
+    pub cap: Duration,
+    /// Number of attempts to allow before giving up and terminating the pod.
+    pub max_attempts: u32,
+}
+
+impl Default for BackoffConfig {
+    fn default() -> Self {
+        BackoffConfig {
+            base: Duration::from_secs(1),
+            multiplier: 2.0,
+            cap: Duration::from_secs(30),
+            max_attempts: 5,
+        }
+    }
+}
+
+/// Upper bound on the delay for the given attempt, before jitter is applied:
+/// `min(cap, base * multiplier^attempt)`. Split out from [`sleep`] so the
+/// exponential/cap math can be unit-tested without actually waiting.
+fn capped_delay(config: &BackoffConfig, attempt: u32) -> Duration {
+    let exp_millis = config.base.as_millis() as f64 * config.multiplier.powi(attempt as i32);
+    let capped_millis = exp_millis.min(config.cap.as_millis() as f64);
+    Duration::from_millis(capped_millis as u64)
+}
+
+/// Sleep for a jittered duration appropriate for the given attempt number
+/// (0-indexed: the first retry after a failure is attempt `0`).
+///
+/// Emits a `tracing` event with the computed delay before sleeping, so the
+/// wait shows up under whichever backoff state's span called this instead of
+/// looking like a silent gap in the trace.
+pub async fn sleep(config: &BackoffConfig, attempt: u32) {
+    let capped_millis = capped_delay(config, attempt).as_millis() as f64;
+    let jittered_millis = if capped_millis > 0.0 {
+        rand::thread_rng().gen_range(0.0, capped_millis)
+    } else {
+        0.0
+    };
+    tracing::debug!(attempt, delay_ms = jittered_millis as u64, "backing off before retry");
+    tokio::time::delay_for(Duration::from_millis(jittered_millis as u64)).await;
+}
+
+/// Tracks retry attempts and the history of prior failures for one backoff
+/// loop, so that giving up can report a summary of everything that was
+/// tried instead of just the most recent error.
+#[derive(Clone, Debug, Default)]
+pub struct BackoffState {
+    /// Number of failures recorded so far.
+    pub attempt: u32,
+    /// Message from each failure, oldest first.
+    pub history: Vec<String>,
+}
+
+impl BackoffState {
+    /// Record a failed attempt.
+    pub fn record_failure(&mut self, message: impl Into<String>) {
+        self.attempt += 1;
+        self.history.push(message.into());
+    }
+
+    /// Whether `max_attempts` has been reached and retrying should stop.
+    pub fn exhausted(&self, config: &BackoffConfig) -> bool {
+        self.attempt >= config.max_attempts
+    }
+
+    /// Clear the attempt count and failure history.
+    ///
+    /// Call this once the pod has successfully exited a backoff loop, so an
+    /// unrelated failure of the same loop later in the pod's lifetime starts
+    /// a fresh retry budget instead of counting against attempts from an
+    /// incident that already resolved.
+    pub fn reset(&mut self) {
+        self.attempt = 0;
+        self.history.clear();
+    }
+
+    /// A human-readable summary of every attempt so far, suitable for a
+    /// `Terminated` status patch's `reason`.
+    pub fn summary(&self) -> String {
+        format!(
+            "gave up after {} attempts: {}",
+            self.history.len(),
+            self.history.join("; ")
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(max_attempts: u32) -> BackoffConfig {
+        BackoffConfig {
+            max_attempts,
+            ..BackoffConfig::default()
+        }
+    }
+
+    #[test]
+    fn not_exhausted_below_max_attempts() {
+        let mut state = BackoffState::default();
+        state.record_failure("first");
+        state.record_failure("second");
+        assert!(!state.exhausted(&config(3)));
+    }
+
+    #[test]
+    fn exhausted_at_the_max_attempts_boundary() {
+        let mut state = BackoffState::default();
+        state.record_failure("first");
+        state.record_failure("second");
+        state.record_failure("third");
+        assert!(state.exhausted(&config(3)));
+    }
+
+    #[test]
+    fn summary_lists_every_failure_oldest_first() {
+        let mut state = BackoffState::default();
+        state.record_failure("first");
+        state.record_failure("second");
+        assert_eq!(state.summary(), "gave up after 2 attempts: first; second");
+    }
+
+    #[test]
+    fn reset_after_recovery_gives_a_later_unrelated_failure_a_fresh_budget() {
+        let mut state = BackoffState::default();
+        let config = config(2);
+
+        state.record_failure("incident A, try 1");
+        state.record_failure("incident A, try 2");
+        assert!(state.exhausted(&config));
+
+        // The pod recovers and runs successfully for a while.
+        state.reset();
+        assert!(!state.exhausted(&config));
+        assert!(state.history.is_empty());
+
+        // Weeks later, an unrelated failure of the same loop shouldn't be
+        // judged against the incident that already resolved.
+        state.record_failure("incident B, try 1");
+        assert!(!state.exhausted(&config));
+    }
+
+    #[test]
+    fn capped_delay_grows_exponentially_until_the_cap() {
+        let config = BackoffConfig {
+            base: Duration::from_millis(10),
+            multiplier: 2.0,
+            cap: Duration::from_millis(50),
+            max_attempts: 5,
+        };
+        assert_eq!(capped_delay(&config, 0), Duration::from_millis(10));
+        assert_eq!(capped_delay(&config, 1), Duration::from_millis(20));
+        assert_eq!(capped_delay(&config, 2), Duration::from_millis(40));
+        assert_eq!(capped_delay(&config, 3), Duration::from_millis(50));
+        assert_eq!(capped_delay(&config, 10), Duration::from_millis(50));
+    }
+
+    #[test]
+    fn first_failure_feeds_attempt_zero_to_the_delay_calculation() {
+        // Mirrors the `record_failure()` -> `backoff_state.attempt` -> sleep
+        // wiring in `default.rs`'s backoff states: `attempt` is the 1-indexed
+        // count of failures recorded so far, so the retry for the failure
+        // that just landed the pod in a backoff state is `attempt - 1`.
+        let config = BackoffConfig {
+            base: Duration::from_millis(10),
+            multiplier: 2.0,
+            cap: Duration::from_millis(1000),
+            max_attempts: 5,
+        };
+        let mut state = BackoffState::default();
+
+        state.record_failure("first failure");
+        assert_eq!(state.attempt, 1);
+        assert_eq!(
+            capped_delay(&config, state.attempt - 1),
+            capped_delay(&config, 0)
+        );
+
+        state.record_failure("second failure");
+        assert_eq!(state.attempt, 2);
+        assert_eq!(
+            capped_delay(&config, state.attempt - 1),
+            capped_delay(&config, 1)
+        );
+    }
+}
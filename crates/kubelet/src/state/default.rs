@@ -1,88 +1,313 @@
 //! Default implementation for state machine graph.
 
+use crate::impl_transitions;
 use crate::pod::Pod;
 use crate::pod::Phase;
 use crate::state;
+use crate::state::backoff;
+use crate::state::backoff::{BackoffConfig, BackoffState};
+use crate::state::event;
+use crate::state::status::ContainerStatus;
+use crate::state::status;
+use crate::state::ProviderState;
 use crate::state::State;
 use crate::state::Transition;
-use log::error;
 use std::sync::Arc;
 
+/// Digests and layers resolved by a successful image pull, handed from
+/// `ImagePull` down to `VolumeMount` and `Starting`.
+#[derive(Clone, Debug, Default)]
+pub struct ImagePullOutput {
+    /// Content digest of each pulled image, one per container.
+    pub digests: Vec<String>,
+    /// Layers that make up the pulled images.
+    pub layers: Vec<String>,
+}
+
+/// Mounted volume paths resolved by `VolumeMount`, handed down to `Starting`.
+#[derive(Clone, Debug, Default)]
+pub struct VolumeMountOutput {
+    /// Host paths the pod's volumes were mounted at.
+    pub mounts: Vec<String>,
+}
+
+/// Everything `Starting` (and, on restart, `Running`) needs in order to
+/// launch the pod's containers without redoing image pull or volume mount.
+#[derive(Clone, Debug, Default)]
+pub struct StartContext {
+    /// Output of the `ImagePull` state.
+    pub image: ImagePullOutput,
+    /// Output of the `VolumeMount` state.
+    pub volumes: VolumeMountOutput,
+}
+
+/// A structured error, carrying both a human-readable message and the name
+/// of the state it originated in, so `Error`'s status patch can report the
+/// real cause instead of a bare `"reason": "Error"`.
+#[derive(Clone, Debug)]
+pub struct ErrorInfo {
+    /// Human-readable description of what went wrong.
+    pub message: String,
+    /// Name of the state the error was raised from.
+    pub source_state: &'static str,
+    /// The init container that failed, if `source_state` was one of the
+    /// `Init*` states.
+    pub container: Option<String>,
+}
+
+impl ErrorInfo {
+    fn from_anyhow(source_state: &'static str, e: &anyhow::Error) -> Self {
+        ErrorInfo {
+            message: e.to_string(),
+            source_state,
+            container: None,
+        }
+    }
+
+    /// Record which init container this error came from.
+    fn with_container(mut self, name: impl Into<String>) -> Self {
+        self.container = Some(name.into());
+        self
+    }
+
+    fn reason(&self) -> String {
+        match &self.container {
+            Some(container) => format!(
+                "{} (from {}, container {})",
+                self.message, self.source_state, container
+            ),
+            None => format!("{} (from {})", self.message, self.source_state),
+        }
+    }
+}
+
 #[async_trait::async_trait]
 /// Trait for implementing default state machine.
-pub trait DefaultStateProvider: 'static + Sync + Send {
+///
+/// Every method is handed the process-wide `SharedState` (behind an `Arc`,
+/// so it's cheap to clone into spawned tasks) and this pod's own `PodState`,
+/// in addition to the `Pod` itself -- see [`ProviderState`].
+pub trait DefaultStateProvider: ProviderState {
     /// A new Pod has been created.
-    async fn registered(&self, _pod: &Pod) -> anyhow::Result<()> {
+    async fn registered(
+        &self,
+        _shared: &Self::SharedState,
+        _pod_state: &mut Self::PodState,
+        _pod: &Pod,
+    ) -> anyhow::Result<()> {
         Ok(())
     }
 
-    /// Pull images for containers.
-    async fn image_pull(&self, _pod: &Pod) -> anyhow::Result<()> {
+    /// Pull images for containers, returning their digests and layers.
+    async fn image_pull(
+        &self,
+        _shared: &Self::SharedState,
+        _pod_state: &mut Self::PodState,
+        _pod: &Pod,
+    ) -> anyhow::Result<ImagePullOutput> {
+        Ok(ImagePullOutput::default())
+    }
+
+    /// Image pull has failed several times. Called once per retry, after
+    /// [`ImagePullBackoff`]'s state has already slept for the configured
+    /// backoff delay.
+    async fn image_pull_backoff(
+        &self,
+        _shared: &Self::SharedState,
+        _pod_state: &mut Self::PodState,
+        _pod: &Pod,
+    ) -> anyhow::Result<()> {
         Ok(())
     }
 
-    /// Image pull has failed several times.
-    async fn image_pull_backoff(&self, _pod: &Pod) -> anyhow::Result<()> {
-        tokio::time::delay_for(std::time::Duration::from_secs(30)).await;
+    /// Mount volumes for containers, returning where they were mounted.
+    async fn volume_mount(
+        &self,
+        _shared: &Self::SharedState,
+        _pod_state: &mut Self::PodState,
+        _pod: &Pod,
+    ) -> anyhow::Result<VolumeMountOutput> {
+        Ok(VolumeMountOutput::default())
+    }
+
+    /// Volume mount has failed several times. Called once per retry, after
+    /// [`VolumeMountBackoff`]'s state has already slept for the configured
+    /// backoff delay.
+    async fn volume_mount_backoff(
+        &self,
+        _shared: &Self::SharedState,
+        _pod_state: &mut Self::PodState,
+        _pod: &Pod,
+    ) -> anyhow::Result<()> {
         Ok(())
     }
 
-    /// Mount volumes for containers.
-    async fn volume_mount(&self, _pod: &Pod) -> anyhow::Result<()> {
+    /// Resolve the pod's init containers, in the declaration order they must
+    /// run in. An empty list means the pod has none, and the state machine
+    /// skips straight from `VolumeMount` to `Starting`.
+    async fn init_container_specs(
+        &self,
+        _shared: &Self::SharedState,
+        _pod_state: &mut Self::PodState,
+        _pod: &Pod,
+    ) -> anyhow::Result<Vec<String>> {
+        Ok(Vec::new())
+    }
+
+    /// Pull and start the named init container.
+    async fn init_container_start(
+        &self,
+        _shared: &Self::SharedState,
+        _pod_state: &mut Self::PodState,
+        _pod: &Pod,
+        _name: &str,
+    ) -> anyhow::Result<()> {
         Ok(())
     }
 
-    /// Volume mount has failed several times.
-    async fn volume_mount_backoff(&self, _pod: &Pod) -> anyhow::Result<()> {
-        tokio::time::delay_for(std::time::Duration::from_secs(30)).await;
+    /// Wait for the named init container to run to completion.
+    async fn init_container_wait(
+        &self,
+        _shared: &Self::SharedState,
+        _pod_state: &mut Self::PodState,
+        _pod: &Pod,
+        _name: &str,
+    ) -> anyhow::Result<()> {
+        Ok(())
+    }
+
+    /// The named init container has failed several times. Called once per
+    /// retry, after [`InitBackoff`]'s state has already slept for the
+    /// configured backoff delay.
+    async fn init_container_backoff(
+        &self,
+        _shared: &Self::SharedState,
+        _pod_state: &mut Self::PodState,
+        _pod: &Pod,
+        _name: &str,
+    ) -> anyhow::Result<()> {
         Ok(())
     }
 
     /// Start containers.
-    async fn starting(&self, _pod: &Pod) -> anyhow::Result<()> {
+    async fn starting(
+        &self,
+        _shared: &Self::SharedState,
+        _pod_state: &mut Self::PodState,
+        _pod: &Pod,
+        _context: &StartContext,
+    ) -> anyhow::Result<()> {
         Ok(())
     }
 
     /// Running state.
-    async fn running(&self, _pod: &Pod) -> anyhow::Result<()> {
+    async fn running(
+        &self,
+        _shared: &Self::SharedState,
+        _pod_state: &mut Self::PodState,
+        _pod: &Pod,
+    ) -> anyhow::Result<()> {
         Ok(())
     }
 
-    /// Handle any errors, on Ok, will transition to Starting again.
-    async fn error(&self, _pod: &Pod) -> anyhow::Result<()> {
-        tokio::time::delay_for(std::time::Duration::from_secs(30)).await;
+    /// Handle any errors, on Ok, will transition to Starting again. Called
+    /// once per retry, after [`Error`]'s state has already slept for the
+    /// configured backoff delay.
+    async fn error(
+        &self,
+        _shared: &Self::SharedState,
+        _pod_state: &mut Self::PodState,
+        _pod: &Pod,
+        _error: &ErrorInfo,
+    ) -> anyhow::Result<()> {
         Ok(())
     }
+
+    /// Report the current status of this pod's containers, used to build an
+    /// accurate `containerStatuses`/`initContainerStatuses` patch. Returns
+    /// `(init_containers, containers)`.
+    async fn container_statuses(
+        &self,
+        _shared: &Self::SharedState,
+        _pod_state: &Self::PodState,
+        _pod: &Pod,
+    ) -> anyhow::Result<(Vec<ContainerStatus>, Vec<ContainerStatus>)> {
+        Ok((Vec::new(), Vec::new()))
+    }
+
+    /// Access this pod's attempt count and failure history for the named
+    /// backoff loop (`"ImagePull"`, `"VolumeMount"`, `"Error"`, or, scoped to
+    /// one init container so independent containers don't share a retry
+    /// budget, `"InitContainer:<name>"` -- see [`init_container_loop`]), so
+    /// it survives across trips in and out of that backoff state.
+    fn backoff_state<'a>(
+        &self,
+        pod_state: &'a mut Self::PodState,
+        loop_name: &str,
+    ) -> &'a mut BackoffState;
+
+    /// The backoff policy to use for the named backoff loop.
+    fn backoff_config(&self, _loop_name: &'static str) -> BackoffConfig {
+        BackoffConfig::default()
+    }
 }
 
 //
-// * Would be nice to support passing types to the next state (error messages, etc.).
 // * We probably need to explore a more concise way for describing status patches.
 // * Can we offer a macro that doesnt require a trait?
 // * How can we expose a nice way for updating container statuses?
 //
 
+// The edges below are the only legal transitions in this graph: each grants
+// the source state a `TransitionTo` impl for its allowed destinations, so
+// `Transition::next::<Self, _>(...)` only compiles for a real edge.
+impl_transitions!(Registered => ImagePull);
+impl_transitions!(ImagePull => VolumeMount);
+impl_transitions!(ImagePullBackoff => ImagePull, Terminated);
+impl_transitions!(VolumeMount => Starting, InitWaiting);
+impl_transitions!(VolumeMountBackoff => VolumeMount, Terminated);
+impl_transitions!(InitWaiting => InitRunning, InitBackoff);
+impl_transitions!(InitRunning => InitTerminated, InitBackoff);
+impl_transitions!(InitTerminated => InitWaiting, Starting);
+impl_transitions!(InitBackoff => InitWaiting, Error);
+impl_transitions!(Starting => Running);
+impl_transitions!(Running => Finished);
+impl_transitions!(Error => Starting, Terminated);
+
+/// The Kubelet is aware of the Pod.
+#[derive(Debug)]
+pub struct Registered;
+
 state!(
-    /// The Kubelet is aware of the Pod.
     Registered,
     DefaultStateProvider,
     ImagePull,
     Error,
     {
-        match provider.registered(pod).await {
-            Ok(_) => Ok(Transition::Advance(ImagePull)),
+        match provider.registered(&shared, pod_state, pod).await {
+            Ok(_) => {
+                let transition = Transition::next::<Self, _>(ImagePull);
+                events.record(pod, "Registered", event::to_state_name(&transition), None, None);
+                Ok(transition)
+            }
             Err(e) => {
-                error!(
-                    "Pod {} encountered an error in state {:?}: {:?}",
-                    pod.name(),
-                    Self,
-                    e
-                );
-                Ok(Transition::Error(Error))
+                let error = ErrorInfo::from_anyhow("Registered", &e);
+                tracing::error!(error = %error.message, "pod state encountered an error");
+                provider
+                    .backoff_state(pod_state, "Error")
+                    .record_failure(error.message.clone());
+                let reason = Some(error.reason());
+                let transition = Transition::error(Error {
+                    first_failure: error.clone(),
+                    error,
+                    resume: None,
+                });
+                events.record(pod, "Registered", event::to_state_name(&transition), reason.clone(), reason);
+                Ok(transition)
             }
         }
     },
-    { 
+    {
         Ok(serde_json::json!(
             {
                 "metadata": {
@@ -95,27 +320,40 @@ state!(
                     "initContainerStatuses": Vec::<()>::new(),
                 }
             }
-        )) 
+        ))
     }
 );
 
+/// The Kubelet is pulling container images.
+#[derive(Debug)]
+pub struct ImagePull;
+
 state!(
-    /// The Kubelet is pulling container images.
     ImagePull,
     DefaultStateProvider,
     VolumeMount,
     ImagePullBackoff,
     {
-        match provider.image_pull(pod).await {
-            Ok(_) => Ok(Transition::Advance(VolumeMount)),
+        match provider.image_pull(&shared, pod_state, pod).await {
+            Ok(image) => {
+                provider.backoff_state(pod_state, "ImagePull").reset();
+                let transition = Transition::next::<Self, _>(VolumeMount { image });
+                events.record(pod, "ImagePull", event::to_state_name(&transition), None, None);
+                Ok(transition)
+            }
             Err(e) => {
-                error!(
-                    "Pod {} encountered an error in state {:?}: {:?}",
-                    pod.name(),
-                    Self,
-                    e
-                );
-                Ok(Transition::Error(ImagePullBackoff))
+                let error = ErrorInfo::from_anyhow("ImagePull", &e);
+                tracing::error!(error = %error.message, "pod state encountered an error");
+                provider
+                    .backoff_state(pod_state, "ImagePull")
+                    .record_failure(error.message.clone());
+                let reason = Some(error.reason());
+                let transition = Transition::error(ImagePullBackoff {
+                    first_failure: error.clone(),
+                    error,
+                });
+                events.record(pod, "ImagePull", event::to_state_name(&transition), reason.clone(), reason);
+                Ok(transition)
             }
         }
     },
@@ -132,27 +370,83 @@ state!(
                     "initContainerStatuses": Vec::<()>::new(),
                 }
             }
-        )) 
+        ))
     }
 );
 
+/// Image pull has failed several times.
+#[derive(Debug)]
+pub struct ImagePullBackoff {
+    /// Why the most recent pull attempt failed.
+    pub error: ErrorInfo,
+    /// The *first* failure that landed the pod in this backoff loop, kept
+    /// distinct from `error` so a consumer watching the transition event
+    /// stream can surface the root cause rather than whatever the latest
+    /// retry happened to fail with.
+    pub first_failure: ErrorInfo,
+}
+
 state!(
-    /// Image pull has failed several times.
     ImagePullBackoff,
     DefaultStateProvider,
     ImagePull,
     ImagePullBackoff,
     {
-        match provider.image_pull_backoff(pod).await {
-            Ok(_) => Ok(Transition::Advance(ImagePull)),
+        let config = provider.backoff_config("ImagePull");
+        let attempt = provider.backoff_state(pod_state, "ImagePull").attempt;
+        tracing::Span::current().record("attempt", &attempt);
+        if provider
+            .backoff_state(pod_state, "ImagePull")
+            .exhausted(&config)
+        {
+            let summary = provider.backoff_state(pod_state, "ImagePull").summary();
+            let transition = Transition::next::<Self, _>(Terminated { reason: summary });
+            events.record(
+                pod,
+                "ImagePullBackoff",
+                event::to_state_name(&transition),
+                None,
+                Some(self.first_failure.reason()),
+            );
+            return Ok(transition);
+        }
+        // `attempt` counts failures recorded so far (at least one, since this
+        // state is only entered after a failure), but `backoff::sleep` wants
+        // the 0-indexed retry number, so the failure that landed us here is
+        // retry `attempt - 1`.
+        backoff::sleep(&config, attempt - 1).await;
+        match provider.image_pull_backoff(&shared, pod_state, pod).await {
+            Ok(_) => {
+                provider.backoff_state(pod_state, "ImagePull").reset();
+                let transition = Transition::next::<Self, _>(ImagePull);
+                events.record(
+                    pod,
+                    "ImagePullBackoff",
+                    event::to_state_name(&transition),
+                    None,
+                    Some(self.first_failure.reason()),
+                );
+                Ok(transition)
+            }
             Err(e) => {
-                error!(
-                    "Pod {} encountered an error in state {:?}: {:?}",
-                    pod.name(),
-                    Self,
-                    e
+                let error = ErrorInfo::from_anyhow("ImagePullBackoff", &e);
+                tracing::error!(error = %error.message, "pod state encountered an error");
+                provider
+                    .backoff_state(pod_state, "ImagePull")
+                    .record_failure(error.message.clone());
+                let reason = Some(error.reason());
+                let transition = Transition::error(ImagePullBackoff {
+                    error,
+                    first_failure: self.first_failure.clone(),
+                });
+                events.record(
+                    pod,
+                    "ImagePullBackoff",
+                    event::to_state_name(&transition),
+                    reason,
+                    Some(self.first_failure.reason()),
                 );
-                Ok(Transition::Error(ImagePullBackoff))
+                Ok(transition)
             }
         }
     },
@@ -169,27 +463,58 @@ state!(
                     "initContainerStatuses": Vec::<()>::new(),
                 }
             }
-        )) 
+        ))
     }
 );
 
+/// The Kubelet is provisioning volumes.
+#[derive(Debug)]
+pub struct VolumeMount {
+    /// Output of the preceding `ImagePull` state.
+    pub image: ImagePullOutput,
+}
+
 state!(
-    /// The Kubelet is provisioning volumes.
     VolumeMount,
     DefaultStateProvider,
-    Starting,
+    InitWaiting,
     VolumeMountBackoff,
     {
-        match provider.volume_mount(pod).await {
-            Ok(_) => Ok(Transition::Advance(Starting)),
+        match provider.volume_mount(&shared, pod_state, pod).await {
+            Ok(volumes) => {
+                provider.backoff_state(pod_state, "VolumeMount").reset();
+                let context = StartContext {
+                    image: self.image,
+                    volumes,
+                };
+                let names = provider.init_container_specs(&shared, pod_state, pod).await?;
+                let transition = if names.is_empty() {
+                    Transition::next::<Self, _>(Starting { context })
+                } else {
+                    Transition::next::<Self, _>(InitWaiting {
+                        context,
+                        names,
+                        index: 0,
+                        statuses: Vec::new(),
+                    })
+                };
+                events.record(pod, "VolumeMount", event::to_state_name(&transition), None, None);
+                Ok(transition)
+            }
             Err(e) => {
-                error!(
-                    "Pod {} encountered an error in state {:?}: {:?}",
-                    pod.name(),
-                    Self,
-                    e
-                );
-                Ok(Transition::Error(VolumeMountBackoff))
+                let error = ErrorInfo::from_anyhow("VolumeMount", &e);
+                tracing::error!(error = %error.message, "pod state encountered an error");
+                provider
+                    .backoff_state(pod_state, "VolumeMount")
+                    .record_failure(error.message.clone());
+                let reason = Some(error.reason());
+                let transition = Transition::error(VolumeMountBackoff {
+                    image: self.image,
+                    first_failure: error.clone(),
+                    error,
+                });
+                events.record(pod, "VolumeMount", event::to_state_name(&transition), reason.clone(), reason);
+                Ok(transition)
             }
         }
     },
@@ -206,27 +531,81 @@ state!(
                     "initContainerStatuses": Vec::<()>::new(),
                 }
             }
-        )) 
+        ))
     }
 );
 
+/// Volume mount has failed several times.
+#[derive(Debug)]
+pub struct VolumeMountBackoff {
+    /// Output of `ImagePull`, carried forward so a retry doesn't re-pull.
+    pub image: ImagePullOutput,
+    /// Why the most recent mount attempt failed.
+    pub error: ErrorInfo,
+    /// The *first* failure that landed the pod in this backoff loop.
+    pub first_failure: ErrorInfo,
+}
+
 state!(
-    /// Volume mount has failed several times.
     VolumeMountBackoff,
     DefaultStateProvider,
     VolumeMount,
     VolumeMountBackoff,
     {
-        match provider.volume_mount_backoff(pod).await {
-            Ok(_) => Ok(Transition::Advance(VolumeMount)),
+        let config = provider.backoff_config("VolumeMount");
+        let attempt = provider.backoff_state(pod_state, "VolumeMount").attempt;
+        tracing::Span::current().record("attempt", &attempt);
+        if provider
+            .backoff_state(pod_state, "VolumeMount")
+            .exhausted(&config)
+        {
+            let summary = provider.backoff_state(pod_state, "VolumeMount").summary();
+            let transition = Transition::next::<Self, _>(Terminated { reason: summary });
+            events.record(
+                pod,
+                "VolumeMountBackoff",
+                event::to_state_name(&transition),
+                None,
+                Some(self.first_failure.reason()),
+            );
+            return Ok(transition);
+        }
+        // See the matching comment in `ImagePullBackoff`: `attempt` is the
+        // 1-indexed failure count, `sleep` wants the 0-indexed retry number.
+        backoff::sleep(&config, attempt - 1).await;
+        match provider.volume_mount_backoff(&shared, pod_state, pod).await {
+            Ok(_) => {
+                provider.backoff_state(pod_state, "VolumeMount").reset();
+                let transition = Transition::next::<Self, _>(VolumeMount { image: self.image });
+                events.record(
+                    pod,
+                    "VolumeMountBackoff",
+                    event::to_state_name(&transition),
+                    None,
+                    Some(self.first_failure.reason()),
+                );
+                Ok(transition)
+            }
             Err(e) => {
-                error!(
-                    "Pod {} encountered an error in state {:?}: {:?}",
-                    pod.name(),
-                    Self,
-                    e
+                let error = ErrorInfo::from_anyhow("VolumeMountBackoff", &e);
+                tracing::error!(error = %error.message, "pod state encountered an error");
+                provider
+                    .backoff_state(pod_state, "VolumeMount")
+                    .record_failure(error.message.clone());
+                let reason = Some(error.reason());
+                let transition = Transition::error(VolumeMountBackoff {
+                    image: self.image,
+                    error,
+                    first_failure: self.first_failure.clone(),
+                });
+                events.record(
+                    pod,
+                    "VolumeMountBackoff",
+                    event::to_state_name(&transition),
+                    reason,
+                    Some(self.first_failure.reason()),
                 );
-                Ok(Transition::Error(VolumeMountBackoff))
+                Ok(transition)
             }
         }
     },
@@ -243,165 +622,591 @@ state!(
                     "initContainerStatuses": Vec::<()>::new(),
                 }
             }
-        )) 
+        ))
+    }
+);
+
+/// The `backoff_state` loop name for the named init container, so that two
+/// independent init containers retrying at the same time don't share one
+/// `max_attempts` budget. The policy itself comes from
+/// `backoff_config("InitContainer")`, shared by every init container.
+fn init_container_loop(name: &str) -> String {
+    format!("InitContainer:{}", name)
+}
+
+/// The Kubelet is about to pull and start the next init container.
+///
+/// Init containers run one at a time, in declaration order; this and
+/// [`InitRunning`]/[`InitTerminated`] loop through `names` by `index` until
+/// all of them have completed, then hand `context` off to [`Starting`].
+#[derive(Debug)]
+pub struct InitWaiting {
+    /// Image and volume info, carried through to `Starting` once every init
+    /// container has completed.
+    pub context: StartContext,
+    /// Init container names, in the order they must run.
+    pub names: Vec<String>,
+    /// Index into `names` of the container this state is waiting on.
+    pub index: usize,
+    /// Statuses of the init containers that have already completed.
+    pub statuses: Vec<ContainerStatus>,
+}
+
+state!(
+    InitWaiting,
+    DefaultStateProvider,
+    InitRunning,
+    InitBackoff,
+    {
+        let name = self.names[self.index].clone();
+        match provider
+            .init_container_start(&shared, pod_state, pod, &name)
+            .await
+        {
+            Ok(_) => {
+                let transition = Transition::next::<Self, _>(InitRunning {
+                    context: self.context,
+                    names: self.names,
+                    index: self.index,
+                    statuses: self.statuses,
+                    name,
+                });
+                events.record(pod, "InitWaiting", event::to_state_name(&transition), None, None);
+                Ok(transition)
+            }
+            Err(e) => {
+                let error = ErrorInfo::from_anyhow("InitWaiting", &e).with_container(name.clone());
+                tracing::error!(error = %error.message, "pod state encountered an error");
+                provider
+                    .backoff_state(pod_state, &init_container_loop(&name))
+                    .record_failure(error.message.clone());
+                let reason = Some(error.reason());
+                let transition = Transition::error(InitBackoff {
+                    context: self.context,
+                    names: self.names,
+                    index: self.index,
+                    statuses: self.statuses,
+                    name,
+                    first_failure: error.clone(),
+                    error,
+                });
+                events.record(pod, "InitWaiting", event::to_state_name(&transition), reason.clone(), reason);
+                Ok(transition)
+            }
+        }
+    },
+    {
+        let mut init_containers = self.statuses.clone();
+        init_containers.push(ContainerStatus {
+            name: self.names[self.index].clone(),
+            ready: false,
+            restart_count: 0,
+            state: status::ContainerState::Waiting {
+                reason: String::new(),
+            },
+        });
+        Ok(status::patch(
+            pod,
+            Phase::Pending,
+            "PodInitializing",
+            &init_containers,
+            &[],
+        ))
+    }
+);
+
+/// The Kubelet is waiting for the current init container to run to
+/// completion.
+#[derive(Debug)]
+pub struct InitRunning {
+    /// Image and volume info, carried through to `Starting`.
+    pub context: StartContext,
+    /// Init container names, in the order they must run.
+    pub names: Vec<String>,
+    /// Index into `names` of the container this state is waiting on.
+    pub index: usize,
+    /// Statuses of the init containers that have already completed.
+    pub statuses: Vec<ContainerStatus>,
+    /// Name of the init container currently running (`names[index]`).
+    pub name: String,
+}
+
+state!(
+    InitRunning,
+    DefaultStateProvider,
+    InitTerminated,
+    InitBackoff,
+    {
+        match provider
+            .init_container_wait(&shared, pod_state, pod, &self.name)
+            .await
+        {
+            Ok(_) => {
+                let mut statuses = self.statuses;
+                statuses.push(ContainerStatus {
+                    name: self.name.clone(),
+                    ready: true,
+                    restart_count: 0,
+                    state: status::ContainerState::Terminated {
+                        reason: "Completed".to_string(),
+                        exit_code: 0,
+                    },
+                });
+                let transition = Transition::next::<Self, _>(InitTerminated {
+                    context: self.context,
+                    names: self.names,
+                    index: self.index,
+                    statuses,
+                });
+                events.record(pod, "InitRunning", event::to_state_name(&transition), None, None);
+                Ok(transition)
+            }
+            Err(e) => {
+                let error =
+                    ErrorInfo::from_anyhow("InitRunning", &e).with_container(self.name.clone());
+                tracing::error!(error = %error.message, "pod state encountered an error");
+                provider
+                    .backoff_state(pod_state, &init_container_loop(&self.name))
+                    .record_failure(error.message.clone());
+                let reason = Some(error.reason());
+                let transition = Transition::error(InitBackoff {
+                    context: self.context,
+                    names: self.names,
+                    index: self.index,
+                    statuses: self.statuses,
+                    name: self.name,
+                    first_failure: error.clone(),
+                    error,
+                });
+                events.record(pod, "InitRunning", event::to_state_name(&transition), reason.clone(), reason);
+                Ok(transition)
+            }
+        }
+    },
+    {
+        let mut init_containers = self.statuses.clone();
+        init_containers.push(ContainerStatus {
+            name: self.name.clone(),
+            ready: false,
+            restart_count: 0,
+            state: status::ContainerState::Running,
+        });
+        Ok(status::patch(
+            pod,
+            Phase::Pending,
+            "PodInitializing",
+            &init_containers,
+            &[],
+        ))
     }
 );
 
+/// The current init container has run to completion; advance to the next
+/// one, or to `Starting` if that was the last one.
+#[derive(Debug)]
+pub struct InitTerminated {
+    /// Image and volume info, carried through to `Starting`.
+    pub context: StartContext,
+    /// Init container names, in the order they must run.
+    pub names: Vec<String>,
+    /// Index into `names` of the container that just completed.
+    pub index: usize,
+    /// Statuses of every init container that has completed so far,
+    /// including the one at `index`.
+    pub statuses: Vec<ContainerStatus>,
+}
+
 state!(
-    /// The Kubelet is starting the containers.
+    InitTerminated,
+    DefaultStateProvider,
     Starting,
+    InitBackoff,
+    {
+        let transition = if self.index + 1 < self.names.len() {
+            Transition::next::<Self, _>(InitWaiting {
+                context: self.context,
+                names: self.names,
+                index: self.index + 1,
+                statuses: self.statuses,
+            })
+        } else {
+            Transition::next::<Self, _>(Starting {
+                context: self.context,
+            })
+        };
+        events.record(pod, "InitTerminated", event::to_state_name(&transition), None, None);
+        Ok(transition)
+    },
+    {
+        Ok(status::patch(
+            pod,
+            Phase::Pending,
+            "PodInitializing",
+            &self.statuses,
+            &[],
+        ))
+    }
+);
+
+/// An init container has failed several times.
+#[derive(Debug)]
+pub struct InitBackoff {
+    /// Image and volume info, carried through to `Starting` once init
+    /// containers eventually succeed.
+    pub context: StartContext,
+    /// Init container names, in the order they must run.
+    pub names: Vec<String>,
+    /// Index into `names` of the container that's failing.
+    pub index: usize,
+    /// Statuses of the init containers that completed before this one.
+    pub statuses: Vec<ContainerStatus>,
+    /// Name of the init container that's failing (`names[index]`).
+    pub name: String,
+    /// Why the most recent attempt failed.
+    pub error: ErrorInfo,
+    /// The *first* failure that landed the pod in this backoff loop.
+    pub first_failure: ErrorInfo,
+}
+
+state!(
+    InitBackoff,
     DefaultStateProvider,
-    Running,
+    InitWaiting,
     Error,
     {
-        match provider.starting(pod).await {
-            Ok(_) => Ok(Transition::Advance(Running)),
+        let config = provider.backoff_config("InitContainer");
+        let loop_name = init_container_loop(&self.name);
+        let attempt = provider.backoff_state(pod_state, &loop_name).attempt;
+        tracing::Span::current().record("attempt", &attempt);
+        if provider
+            .backoff_state(pod_state, &loop_name)
+            .exhausted(&config)
+        {
+            let reason = Some(self.first_failure.reason());
+            provider
+                .backoff_state(pod_state, "Error")
+                .record_failure(self.first_failure.message.clone());
+            let transition = Transition::next::<Self, _>(Error {
+                first_failure: self.first_failure.clone(),
+                error: self.first_failure,
+                resume: Some(self.context),
+            });
+            events.record(pod, "InitBackoff", event::to_state_name(&transition), None, reason);
+            return Ok(transition);
+        }
+        // See the matching comment in `ImagePullBackoff`: `attempt` is the
+        // 1-indexed failure count, `sleep` wants the 0-indexed retry number.
+        backoff::sleep(&config, attempt - 1).await;
+        match provider
+            .init_container_backoff(&shared, pod_state, pod, &self.name)
+            .await
+        {
+            Ok(_) => {
+                let transition = Transition::next::<Self, _>(InitWaiting {
+                    context: self.context,
+                    names: self.names,
+                    index: self.index,
+                    statuses: self.statuses,
+                });
+                events.record(
+                    pod,
+                    "InitBackoff",
+                    event::to_state_name(&transition),
+                    None,
+                    Some(self.first_failure.reason()),
+                );
+                Ok(transition)
+            }
             Err(e) => {
-                error!(
-                    "Pod {} encountered an error in state {:?}: {:?}",
-                    pod.name(),
-                    Self,
-                    e
+                let error =
+                    ErrorInfo::from_anyhow("InitBackoff", &e).with_container(self.name.clone());
+                tracing::error!(error = %error.message, "pod state encountered an error");
+                provider
+                    .backoff_state(pod_state, &loop_name)
+                    .record_failure(error.message.clone());
+                let reason = Some(error.reason());
+                let transition = Transition::error(InitBackoff {
+                    context: self.context,
+                    names: self.names,
+                    index: self.index,
+                    statuses: self.statuses,
+                    name: self.name,
+                    error,
+                    first_failure: self.first_failure.clone(),
+                });
+                events.record(
+                    pod,
+                    "InitBackoff",
+                    event::to_state_name(&transition),
+                    reason,
+                    Some(self.first_failure.reason()),
                 );
-                Ok(Transition::Error(Error))
+                Ok(transition)
             }
         }
     },
     {
-        Ok(serde_json::json!(
-            {
-                "metadata": {
-                    "resourceVersion": "",
-                },
-                "status": {
-                    "phase": Phase::Pending,
-                    "reason": "Starting",
-                    "containerStatuses": Vec::<()>::new(),
-                    "initContainerStatuses": Vec::<()>::new(),
-                }
+        let mut init_containers = self.statuses.clone();
+        init_containers.push(ContainerStatus {
+            name: self.name.clone(),
+            ready: false,
+            restart_count: 0,
+            state: status::ContainerState::Waiting {
+                reason: "Error".to_string(),
+            },
+        });
+        Ok(status::patch(
+            pod,
+            Phase::Pending,
+            "PodInitializing",
+            &init_containers,
+            &[],
+        ))
+    }
+);
+
+/// The Kubelet is starting the containers.
+#[derive(Debug)]
+pub struct Starting {
+    /// Image and volume info resolved by the preceding states.
+    pub context: StartContext,
+}
+
+state!(
+    Starting,
+    DefaultStateProvider,
+    Running,
+    Error,
+    {
+        match provider.starting(&shared, pod_state, pod, &self.context).await {
+            Ok(_) => {
+                provider.backoff_state(pod_state, "Error").reset();
+                let transition = Transition::next::<Self, _>(Running {
+                    context: self.context,
+                });
+                events.record(pod, "Starting", event::to_state_name(&transition), None, None);
+                Ok(transition)
+            }
+            Err(e) => {
+                let error = ErrorInfo::from_anyhow("Starting", &e);
+                tracing::error!(error = %error.message, "pod state encountered an error");
+                provider
+                    .backoff_state(pod_state, "Error")
+                    .record_failure(error.message.clone());
+                let reason = Some(error.reason());
+                let transition = Transition::error(Error {
+                    first_failure: error.clone(),
+                    error,
+                    resume: Some(self.context),
+                });
+                events.record(pod, "Starting", event::to_state_name(&transition), reason.clone(), reason);
+                Ok(transition)
             }
-        )) 
+        }
+    },
+    {
+        let (init_containers, containers) = provider
+            .container_statuses(&shared, pod_state, pod)
+            .await?;
+        Ok(status::patch(
+            pod,
+            Phase::Pending,
+            "Starting",
+            &init_containers,
+            &containers,
+        ))
     }
 );
 
+/// The pod's containers are running.
+#[derive(Debug)]
+pub struct Running {
+    /// Image and volume info, kept in case a failure here needs to restart.
+    pub context: StartContext,
+}
+
 state!(
-    /// The Kubelet is provisioning volumes.
     Running,
     DefaultStateProvider,
     Finished,
     Error,
     {
-        match provider.running(pod).await {
-            Ok(_) => Ok(Transition::Advance(Finished)),
+        match provider.running(&shared, pod_state, pod).await {
+            Ok(_) => {
+                provider.backoff_state(pod_state, "Error").reset();
+                let transition = Transition::next::<Self, _>(Finished);
+                events.record(pod, "Running", event::to_state_name(&transition), None, None);
+                Ok(transition)
+            }
             Err(e) => {
-                error!(
-                    "Pod {} encountered an error in state {:?}: {:?}",
-                    pod.name(),
-                    Self,
-                    e
-                );
-                Ok(Transition::Error(Error))
+                let error = ErrorInfo::from_anyhow("Running", &e);
+                tracing::error!(error = %error.message, "pod state encountered an error");
+                provider
+                    .backoff_state(pod_state, "Error")
+                    .record_failure(error.message.clone());
+                let reason = Some(error.reason());
+                let transition = Transition::error(Error {
+                    first_failure: error.clone(),
+                    error,
+                    resume: Some(self.context),
+                });
+                events.record(pod, "Running", event::to_state_name(&transition), reason.clone(), reason);
+                Ok(transition)
             }
         }
     },
     {
-        Ok(serde_json::json!(
-            {
-                "metadata": {
-                    "resourceVersion": "",
-                },
-                "status": {
-                    "phase": Phase::Running,
-                    "reason": "Running",
-                    "containerStatuses": Vec::<()>::new(),
-                    "initContainerStatuses": Vec::<()>::new(),
-                }
-            }
-        )) 
+        let (init_containers, containers) = provider
+            .container_statuses(&shared, pod_state, pod)
+            .await?;
+        Ok(status::patch(
+            pod,
+            Phase::Running,
+            "Running",
+            &init_containers,
+            &containers,
+        ))
     }
 );
 
+/// The Pod encountered an error.
+#[derive(Debug)]
+pub struct Error {
+    /// The error that caused this transition.
+    pub error: ErrorInfo,
+    /// The *first* failure that landed the pod in this error loop, which is
+    /// the one surfaced as the root cause in the status patch and the
+    /// transition event stream -- not whichever retry most recently failed.
+    pub first_failure: ErrorInfo,
+    /// Image/volume context to resume with, if the error happened after
+    /// `VolumeMount` completed.
+    pub resume: Option<StartContext>,
+}
+
 state!(
-    /// The Pod encountered an error.
     Error,
     DefaultStateProvider,
     Starting,
     Error,
     {
-        match provider.error(pod).await {
-            Ok(_) => Ok(Transition::Advance(Starting)),
+        let config = provider.backoff_config("Error");
+        let attempt = provider.backoff_state(pod_state, "Error").attempt;
+        tracing::Span::current().record("attempt", &attempt);
+        if provider.backoff_state(pod_state, "Error").exhausted(&config) {
+            let summary = provider.backoff_state(pod_state, "Error").summary();
+            let transition = Transition::next::<Self, _>(Terminated { reason: summary });
+            events.record(
+                pod,
+                "Error",
+                event::to_state_name(&transition),
+                None,
+                Some(self.first_failure.reason()),
+            );
+            return Ok(transition);
+        }
+        // See the matching comment in `ImagePullBackoff`: `attempt` is the
+        // 1-indexed failure count, `sleep` wants the 0-indexed retry number.
+        backoff::sleep(&config, attempt - 1).await;
+        match provider.error(&shared, pod_state, pod, &self.error).await {
+            Ok(_) => {
+                provider.backoff_state(pod_state, "Error").reset();
+                let transition = Transition::next::<Self, _>(Starting {
+                    context: self.resume.unwrap_or_default(),
+                });
+                events.record(
+                    pod,
+                    "Error",
+                    event::to_state_name(&transition),
+                    None,
+                    Some(self.first_failure.reason()),
+                );
+                Ok(transition)
+            }
             Err(e) => {
-                error!(
-                    "Pod {} encountered an error in state {:?}: {:?}",
-                    pod.name(),
-                    Self,
-                    e
+                let error = ErrorInfo::from_anyhow("Error", &e);
+                tracing::error!(error = %error.message, "pod state encountered an error");
+                provider
+                    .backoff_state(pod_state, "Error")
+                    .record_failure(error.message.clone());
+                let reason = Some(error.reason());
+                let transition = Transition::error(Error {
+                    error,
+                    first_failure: self.first_failure.clone(),
+                    resume: self.resume,
+                });
+                events.record(
+                    pod,
+                    "Error",
+                    event::to_state_name(&transition),
+                    reason,
+                    Some(self.first_failure.reason()),
                 );
-                Ok(Transition::Error(Error))
+                Ok(transition)
             }
         }
     },
     {
-        Ok(serde_json::json!(
-            {
-                "metadata": {
-                    "resourceVersion": "",
-                },
-                "status": {
-                    "phase": Phase::Failed,
-                    "reason": "Error",
-                    "containerStatuses": Vec::<()>::new(),
-                    "initContainerStatuses": Vec::<()>::new(),
-                }
-            }
-        )) 
+        let (init_containers, containers) = provider
+            .container_statuses(&shared, pod_state, pod)
+            .await?;
+        Ok(status::patch(
+            pod,
+            Phase::Failed,
+            &format!("Error: {}", self.first_failure.reason()),
+            &init_containers,
+            &containers,
+        ))
     }
 );
 
+/// The Pod was terminated before it completed.
+#[derive(Debug)]
+pub struct Terminated {
+    /// Why the pod was terminated, e.g. a backoff loop's attempt history
+    /// once it ran out of retries.
+    pub reason: String,
+}
+
 state!(
-    /// The Pod was terminated before it completed.
     Terminated,
     DefaultStateProvider,
     Terminated,
     Terminated,
     { Ok(Transition::Complete(Ok(()))) },
     {
-        Ok(serde_json::json!(
-            {
-                "metadata": {
-                    "resourceVersion": "",
-                },
-                "status": {
-                    "phase": Phase::Failed,
-                    "reason": "Failed",
-                    "containerStatuses": Vec::<()>::new(),
-                    "initContainerStatuses": Vec::<()>::new(),
-                }
-            }
-        )) 
+        let (init_containers, containers) = provider
+            .container_statuses(&shared, pod_state, pod)
+            .await?;
+        Ok(status::patch(
+            pod,
+            Phase::Failed,
+            &self.reason,
+            &init_containers,
+            &containers,
+        ))
     }
 );
 
+/// The Pod completed execution with no errors.
+#[derive(Debug)]
+pub struct Finished;
+
 state!(
-    /// The Pod completed execution with no errors.
     Finished,
     DefaultStateProvider,
     Finished,
     Finished,
     { Ok(Transition::Complete(Ok(()))) },
     {
-        Ok(serde_json::json!(
-            {
-                "metadata": {
-                    "resourceVersion": "",
-                },
-                "status": {
-                    "phase": Phase::Succeeded,
-                    "reason": "Failed",
-                    "containerStatuses": Vec::<()>::new(),
-                    "initContainerStatuses": Vec::<()>::new(),
-                }
-            }
-        )) 
+        let (init_containers, containers) = provider
+            .container_statuses(&shared, pod_state, pod)
+            .await?;
+        Ok(status::patch(
+            pod,
+            Phase::Succeeded,
+            "Succeeded",
+            &init_containers,
+            &containers,
+        ))
     }
 );
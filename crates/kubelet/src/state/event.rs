@@ -0,0 +1,74 @@
+//! An observable stream of state machine transitions.
+//!
+//! Modeled on how `cloudformatious` streams stack events: instead of
+//! grepping logs to find out how a pod is progressing, a consumer can
+//! subscribe to a [`TransitionEvents`] channel and get a `Stream` of
+//! [`TransitionEvent`]s to log, turn into metrics, or emit as Kubernetes
+//! Events.
+
+use crate::pod::Pod;
+use crate::state::{ProviderState, Transition};
+
+/// One transition in a pod's state machine.
+#[derive(Clone, Debug)]
+pub struct TransitionEvent {
+    /// Name of the pod this transition belongs to.
+    pub pod: String,
+    /// State the pod transitioned out of.
+    pub from_state: &'static str,
+    /// State the pod transitioned into.
+    pub to_state: &'static str,
+    /// When the transition happened.
+    pub timestamp: std::time::SystemTime,
+    /// Human-readable reason for the transition, if any.
+    pub reason: Option<String>,
+    /// The *first* failure that led to this transition, if the pod has been
+    /// bouncing through a backoff or error loop -- not just the latest one.
+    /// This lets a consumer surface the root cause the way
+    /// `StackFailure.stack_status_reason` does, instead of only ever seeing
+    /// whatever the most recent retry failed with.
+    pub error: Option<String>,
+}
+
+/// Returns the name of the state a [`Transition`] leads into.
+pub fn to_state_name<P: ProviderState>(transition: &Transition<P>) -> &'static str {
+    match transition {
+        Transition::Next(holder) => holder.name(),
+        Transition::Error(state) => state.name(),
+        Transition::Complete(_) => "Complete",
+    }
+}
+
+/// Handle for recording [`TransitionEvent`]s, cheaply cloneable since it's
+/// backed by an unbounded channel sender.
+#[derive(Clone)]
+pub struct TransitionEvents {
+    tx: tokio::sync::mpsc::UnboundedSender<TransitionEvent>,
+}
+
+impl TransitionEvents {
+    /// Create a new emitter and the receiving end of its event stream.
+    pub fn channel() -> (Self, tokio::sync::mpsc::UnboundedReceiver<TransitionEvent>) {
+        let (tx, rx) = tokio::sync::mpsc::unbounded_channel();
+        (TransitionEvents { tx }, rx)
+    }
+
+    /// Record a transition. A no-op if nobody is listening.
+    pub fn record(
+        &self,
+        pod: &Pod,
+        from_state: &'static str,
+        to_state: &'static str,
+        reason: Option<String>,
+        error: Option<String>,
+    ) {
+        let _ = self.tx.send(TransitionEvent {
+            pod: pod.name().to_string(),
+            from_state,
+            to_state,
+            timestamp: std::time::SystemTime::now(),
+            reason,
+            error,
+        });
+    }
+}
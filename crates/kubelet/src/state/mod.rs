@@ -0,0 +1,224 @@
+//! Core types for building a Pod state machine out of the `state!` macro.
+//!
+//! Every state runs inside a `tracing` span: [`pod_span`] opens one root span
+//! per pod when its machine starts, and [`state!`] opens a child span per
+//! state execution, so transitions, backoff sleeps, and errors all show up
+//! correlated under one trace instead of needing to be pieced back together
+//! from flat logs.
+
+use crate::pod::Pod;
+use std::sync::Arc;
+
+pub mod backoff;
+pub mod default;
+pub mod event;
+pub mod status;
+
+use event::TransitionEvents;
+
+/// Opens the root span for one pod's state machine run.
+///
+/// Create this once, when a pod's machine starts, and pass it to every
+/// [`State::next`] call for that pod -- [`state!`] opens a child span from it
+/// for each state's execution, labeled with the state name and attempt
+/// number, so a consumer can filter a trace backend by `pod.namespace`/
+/// `pod.name` and see the whole run, instead of grepping flat logs for a pod
+/// name across every state.
+pub fn pod_span(pod: &Pod) -> tracing::Span {
+    tracing::info_span!(
+        "pod_state_machine",
+        pod.namespace = %pod.namespace(),
+        pod.name = %pod.name(),
+    )
+}
+
+/// A provider that supplies process-wide shared state plus a per-pod mutable
+/// state object to every state in its state machine.
+///
+/// `SharedState` is created once by the caller driving the state machines and
+/// handed to every pod's machine behind an `Arc`, so e.g. an image-pull
+/// implementation can maintain a process-wide layer cache or a backoff state
+/// can share a global rate limiter. `PodState` is created once per pod and is
+/// exclusive to that pod's machine, for things like open volume handles.
+pub trait ProviderState: 'static + Sync + Send {
+    /// State shared, behind an `Arc`, across every pod's state machine.
+    type SharedState: Send + Sync + 'static;
+    /// Mutable state private to one pod's state machine.
+    type PodState: Send + Sync + 'static;
+}
+
+/// A single state in a Pod's state machine.
+///
+/// Implementors are generated by the [`state!`] macro; you should not need
+/// to implement this by hand.
+#[async_trait::async_trait]
+pub trait State<P: ProviderState>: Sync + Send + std::fmt::Debug {
+    /// Short, stable name for this state, used in transition events and logs.
+    fn name(&self) -> &'static str;
+
+    /// Run this state to completion and return the transition to take next.
+    ///
+    /// `span` is the pod's root span from [`pod_span`]; implementations (via
+    /// [`state!`]) nest a per-state child span from it so this execution's
+    /// logs and timing are correlated with the rest of the pod's run.
+    async fn next(
+        self: Box<Self>,
+        provider: Arc<P>,
+        shared: Arc<P::SharedState>,
+        pod_state: &mut P::PodState,
+        pod: &Pod,
+        events: &TransitionEvents,
+        span: &tracing::Span,
+    ) -> anyhow::Result<Transition<P>>;
+
+    /// Compute the Kubernetes status patch that corresponds to this state.
+    async fn json_status(
+        &self,
+        provider: Arc<P>,
+        shared: Arc<P::SharedState>,
+        pod_state: &P::PodState,
+        pod: &Pod,
+    ) -> anyhow::Result<serde_json::Value>;
+}
+
+/// Marks that `Self` is allowed to advance to `S` in the state graph.
+///
+/// This is implemented for a pair of states with [`impl_transitions!`], which
+/// is the only supported way to create an edge. [`StateHolder`]'s field is
+/// private, so the only way to build a `Transition::Next` is through
+/// [`Transition::next`], and that function can't be called unless this trait
+/// is implemented for the `(current, next)` pair. An attempt to advance to a
+/// state that isn't wired into the graph is therefore a compile error instead
+/// of a bug that only shows up at runtime.
+pub trait TransitionTo<S> {}
+
+/// Holds the next state to run, constructed only via [`Transition::next`].
+///
+/// The inner field is private so that a `Transition::Next` can never be
+/// built by hand, only through a statically-checked [`TransitionTo`] edge.
+pub struct StateHolder<P: ProviderState> {
+    state: Box<dyn State<P>>,
+}
+
+/// The outcome of running a single state.
+pub enum Transition<P: ProviderState> {
+    /// Advance to a new state that this state is allowed to transition to.
+    Next(StateHolder<P>),
+    /// Move to an error-handling state.
+    Error(Box<dyn State<P>>),
+    /// The state machine is finished.
+    Complete(anyhow::Result<()>),
+}
+
+impl<P: ProviderState> Transition<P> {
+    /// Advance to `next`, carrying whatever payload `next` was constructed
+    /// with. Only possible when `Cur: TransitionTo<Next>` -- that is, when
+    /// this is a real edge in the state graph. `Cur` is a type-only marker
+    /// (typically `Self`), passed explicitly since a state carrying its
+    /// predecessor's payload can't always conjure a throwaway value of its
+    /// own type: `Transition::next::<Self, _>(next)`.
+    pub fn next<Cur, Next>(next: Next) -> Self
+    where
+        Cur: TransitionTo<Next>,
+        Next: State<P>,
+    {
+        Transition::Next(StateHolder {
+            state: Box::new(next),
+        })
+    }
+
+    /// Move to an error-handling state. Unlike [`Transition::next`], this is
+    /// not restricted by [`TransitionTo`]: any state may report an error.
+    pub fn error<S: State<P> + 'static>(error_state: S) -> Self {
+        Transition::Error(Box::new(error_state))
+    }
+}
+
+impl<P: ProviderState> std::ops::Deref for StateHolder<P> {
+    type Target = dyn State<P>;
+
+    fn deref(&self) -> &Self::Target {
+        &*self.state
+    }
+}
+
+impl<P: ProviderState> StateHolder<P> {
+    /// Consume the holder, yielding the boxed state it carries.
+    pub fn into_inner(self) -> Box<dyn State<P>> {
+        self.state
+    }
+}
+
+/// Implements [`State`] for an already-declared `$name` type, whose `next`
+/// and `json_status` bodies are the two blocks passed in.
+///
+/// `$name` is no longer declared by this macro: now that states carry their
+/// predecessor's payload as fields (see `ImagePullOutput`, `StartContext`,
+/// etc. in `default.rs`), each state's shape differs, so the struct (and its
+/// doc comment and `#[derive(Debug)]`) is written out above the `state!`
+/// call instead. The `$next`/`$error` types remain purely documentation --
+/// the actual edges allowed out of `$name` are whatever [`impl_transitions!`]
+/// grants it via [`TransitionTo`].
+#[macro_export]
+macro_rules! state {
+    (
+        $name:ty,
+        $provider:ty,
+        $next:ty,
+        $error:ty,
+        $next_fn:block,
+        $status_fn:block
+    ) => {
+        #[async_trait::async_trait]
+        impl $crate::state::State<$provider> for $name {
+            fn name(&self) -> &'static str {
+                stringify!($name)
+            }
+
+            #[allow(unused_variables)]
+            async fn next(
+                self: Box<Self>,
+                provider: std::sync::Arc<$provider>,
+                shared: std::sync::Arc<<$provider as $crate::state::ProviderState>::SharedState>,
+                pod_state: &mut <$provider as $crate::state::ProviderState>::PodState,
+                pod: &$crate::pod::Pod,
+                events: &$crate::state::event::TransitionEvents,
+                span: &tracing::Span,
+            ) -> anyhow::Result<$crate::state::Transition<$provider>> {
+                use tracing::Instrument;
+
+                let state_span = tracing::info_span!(
+                    parent: span,
+                    "state",
+                    name = stringify!($name),
+                    pod.namespace = %pod.namespace(),
+                    pod.name = %pod.name(),
+                    attempt = tracing::field::Empty,
+                );
+                async move { $next_fn }.instrument(state_span).await
+            }
+
+            #[allow(unused_variables)]
+            async fn json_status(
+                &self,
+                provider: std::sync::Arc<$provider>,
+                shared: std::sync::Arc<<$provider as $crate::state::ProviderState>::SharedState>,
+                pod_state: &<$provider as $crate::state::ProviderState>::PodState,
+                pod: &$crate::pod::Pod,
+            ) -> anyhow::Result<serde_json::Value> {
+                $status_fn
+            }
+        }
+    };
+}
+
+/// Grants `$from` a [`TransitionTo`] edge to each of `$to`, so that
+/// `Transition::next::<$from, _>(<one of $to>)` type-checks.
+#[macro_export]
+macro_rules! impl_transitions {
+    ($from:ty => $($to:ty),+ $(,)?) => {
+        $(
+            impl $crate::state::TransitionTo<$to> for $from {}
+        )+
+    };
+}
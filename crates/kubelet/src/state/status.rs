@@ -0,0 +1,240 @@
+//! Computes `kubectl`-accurate container statuses and summary reasons for a
+//! pod's status patch, instead of the placeholder
+//! `"containerStatuses": Vec::<()>::new()` every state used to emit.
+//!
+//! The algorithm mirrors what `kubectl get pods` derives from a pod's
+//! `containerStatuses`/`initContainerStatuses`: start from the phase (or
+//! `pod.status.reason`, if the pod already has one), then let the
+//! container states override it -- with init containers taking priority and
+//! rendered as `Init:<reason>`/`Init:N/M` while they're still running.
+
+use crate::pod::{Phase, Pod};
+
+/// The lifecycle state `kubectl` distinguishes for a single container.
+#[derive(Clone, Debug)]
+pub enum ContainerState {
+    /// Not yet running; `reason` is e.g. `ContainerCreating`, `CrashLoopBackOff`.
+    Waiting {
+        /// Why the container isn't running yet.
+        reason: String,
+    },
+    /// Running normally.
+    Running,
+    /// Exited; `reason` is e.g. `Completed`, `Error`, or empty if none was set.
+    Terminated {
+        /// Why the container exited, if known.
+        reason: String,
+        /// Process exit code.
+        exit_code: i32,
+    },
+}
+
+/// One container's status, as reported by the provider.
+#[derive(Clone, Debug)]
+pub struct ContainerStatus {
+    /// Container name, matching the pod spec.
+    pub name: String,
+    /// Whether the container currently passes its readiness check.
+    pub ready: bool,
+    /// Number of times this container has been restarted.
+    pub restart_count: i32,
+    /// Current lifecycle state.
+    pub state: ContainerState,
+}
+
+impl ContainerStatus {
+    fn to_json(&self) -> serde_json::Value {
+        let state = match &self.state {
+            ContainerState::Waiting { reason } => serde_json::json!({ "waiting": { "reason": reason } }),
+            ContainerState::Running => serde_json::json!({ "running": {} }),
+            ContainerState::Terminated { reason, exit_code } => {
+                serde_json::json!({ "terminated": { "reason": reason, "exitCode": exit_code } })
+            }
+        };
+        serde_json::json!({
+            "name": self.name,
+            "ready": self.ready,
+            "restartCount": self.restart_count,
+            "state": state,
+        })
+    }
+}
+
+/// Build a Kubernetes status patch with real `containerStatuses` and a
+/// `kubectl`-style summary `reason`.
+///
+/// `default_reason` is used only when neither `pod.status.reason` nor any
+/// container's state has something more specific to say (e.g. a state with
+/// no containers running yet, like `Registered`).
+pub fn patch(
+    pod: &Pod,
+    phase: Phase,
+    default_reason: &str,
+    init_containers: &[ContainerStatus],
+    containers: &[ContainerStatus],
+) -> serde_json::Value {
+    let reason = summary_reason(pod, default_reason, init_containers, containers);
+    serde_json::json!(
+        {
+            "metadata": {
+                "resourceVersion": "",
+            },
+            "status": {
+                "phase": phase,
+                "reason": reason,
+                "containerStatuses": containers.iter().map(ContainerStatus::to_json).collect::<Vec<_>>(),
+                "initContainerStatuses": init_containers.iter().map(ContainerStatus::to_json).collect::<Vec<_>>(),
+            }
+        }
+    )
+}
+
+fn summary_reason(
+    pod: &Pod,
+    default_reason: &str,
+    init_containers: &[ContainerStatus],
+    containers: &[ContainerStatus],
+) -> String {
+    reason_from_statuses(pod.status_reason(), default_reason, init_containers, containers)
+}
+
+/// The phase/reason precedence at the core of [`summary_reason`], split out
+/// so it can be unit-tested without needing a real [`Pod`].
+fn reason_from_statuses(
+    status_reason: Option<String>,
+    default_reason: &str,
+    init_containers: &[ContainerStatus],
+    containers: &[ContainerStatus],
+) -> String {
+    let mut reason = status_reason.unwrap_or_else(|| default_reason.to_string());
+
+    // While any init container hasn't successfully finished, its status
+    // drives the summary reason and the main containers are not yet running.
+    let total_init = init_containers.len();
+    for (i, container) in init_containers.iter().enumerate() {
+        match &container.state {
+            ContainerState::Terminated { exit_code: 0, .. } => continue,
+            ContainerState::Terminated { exit_code, .. } => {
+                return format!("Init:ExitCode:{}", exit_code)
+            }
+            ContainerState::Waiting { reason } if !reason.is_empty() => {
+                return format!("Init:{}", reason)
+            }
+            _ => return format!("Init:{}/{}", i, total_init),
+        }
+    }
+
+    for container in containers {
+        match &container.state {
+            ContainerState::Waiting { reason: r } if !r.is_empty() => reason = r.clone(),
+            ContainerState::Terminated { reason: r, exit_code } => {
+                reason = if !r.is_empty() {
+                    r.clone()
+                } else {
+                    format!("ExitCode:{}", exit_code)
+                };
+            }
+            _ => {}
+        }
+    }
+
+    reason
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn waiting(reason: &str) -> ContainerStatus {
+        ContainerStatus {
+            name: "container".to_string(),
+            ready: false,
+            restart_count: 0,
+            state: ContainerState::Waiting {
+                reason: reason.to_string(),
+            },
+        }
+    }
+
+    fn terminated(reason: &str, exit_code: i32) -> ContainerStatus {
+        ContainerStatus {
+            name: "container".to_string(),
+            ready: false,
+            restart_count: 0,
+            state: ContainerState::Terminated {
+                reason: reason.to_string(),
+                exit_code,
+            },
+        }
+    }
+
+    #[test]
+    fn falls_back_to_the_default_reason_with_no_containers() {
+        assert_eq!(
+            reason_from_statuses(None, "Registered", &[], &[]),
+            "Registered"
+        );
+    }
+
+    #[test]
+    fn pod_status_reason_wins_over_the_default() {
+        assert_eq!(
+            reason_from_statuses(Some("Evicted".to_string()), "Running", &[], &[]),
+            "Evicted"
+        );
+    }
+
+    #[test]
+    fn waiting_init_container_without_its_own_reason_reports_progress() {
+        let init = vec![waiting("")];
+        assert_eq!(
+            reason_from_statuses(None, "PodInitializing", &init, &[]),
+            "Init:0/1"
+        );
+    }
+
+    #[test]
+    fn waiting_init_container_with_a_reason_is_surfaced_directly() {
+        let init = vec![waiting("ImagePullBackOff")];
+        assert_eq!(
+            reason_from_statuses(None, "PodInitializing", &init, &[]),
+            "Init:ImagePullBackOff"
+        );
+    }
+
+    #[test]
+    fn init_container_terminated_successfully_is_skipped() {
+        let init = vec![terminated("Completed", 0)];
+        assert_eq!(
+            reason_from_statuses(None, "Starting", &init, &[]),
+            "Starting"
+        );
+    }
+
+    #[test]
+    fn second_init_container_waiting_reports_its_own_index() {
+        let init = vec![terminated("Completed", 0), waiting("")];
+        assert_eq!(
+            reason_from_statuses(None, "PodInitializing", &init, &[]),
+            "Init:1/2"
+        );
+    }
+
+    #[test]
+    fn init_container_terminated_with_failure_reports_its_exit_code() {
+        let init = vec![terminated("Error", 1)];
+        assert_eq!(
+            reason_from_statuses(None, "PodInitializing", &init, &[]),
+            "Init:ExitCode:1"
+        );
+    }
+
+    #[test]
+    fn container_terminated_without_a_reason_reports_its_exit_code() {
+        let containers = vec![terminated("", 137)];
+        assert_eq!(
+            reason_from_statuses(None, "Running", &[], &containers),
+            "ExitCode:137"
+        );
+    }
+}